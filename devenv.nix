@@ -42,6 +42,50 @@
     devenv init "$tmp"
     pushd "$tmp"
       devenv ci
+
+      # exit codes of scripts run in the environment are propagated
+      status=0
+      devenv shell --command 'exit 3' || status=$?
+      [ "$status" -eq 3 ]
+      status=0
+      devenv shell --command 'exit "$1"' 5 || status=$?
+      [ "$status" -eq 5 ]
+      echo 'exit "$1"' > script.sh
+      status=0
+      devenv shell --command-file script.sh 4 || status=$?
+      [ "$status" -eq 4 ]
+
+      devenv update --keep-going
+    popd
+    rm -rf "$tmp"
+
+    # update --keep-going refuses to run without a lock to start from
+    tmp="$(mktemp -d)"
+    devenv init "$tmp"
+    pushd "$tmp"
+      status=0
+      output=$(devenv update --keep-going 2>&1) || status=$?
+      [ "$status" -eq 1 ]
+      grep -q "needs an existing devenv.lock" <<< "$output"
+    popd
+    rm -rf "$tmp"
+
+    # the devenv.yaml schema accepts the file devenv init scaffolds
+    tmp="$(mktemp -d)"
+    devenv generate-yaml-schema > "$tmp/schema.json"
+    ${pkgs.yaml2json}/bin/yaml2json < examples/simple/devenv.yaml > "$tmp/devenv.json"
+    ${pkgs.python3Packages.jsonschema}/bin/jsonschema --instance "$tmp/devenv.json" "$tmp/schema.json"
+    rm -rf "$tmp"
+
+    # a second devenv up should fail fast while one is running
+    tmp="$(mktemp -d)"
+    pushd "$tmp"
+      mkdir -p .devenv
+      echo 12345 > .devenv/up.lock
+      status=0
+      output=$(${pkgs.flock}/bin/flock .devenv/up.lock devenv up 2>&1) || status=$?
+      [ "$status" -eq 1 ]
+      grep -q "devenv up is already running (pid 12345)" <<< "$output"
     popd
     rm -rf "$tmp"
 
@@ -68,8 +68,23 @@ pkgs.writeScriptBin "devenv" ''
 
   case $command in
     up)
+      # lock before assembling so a second instance doesn't touch our state
+      lockfile="$(pwd)/.devenv/up.lock"
+      mkdir -p "$(pwd)/.devenv"
+      # the OS releases the lock when this process exits, even if it crashes
+      exec 9>>"$lockfile"
+      if ! ${pkgs.flock}/bin/flock -n 9; then
+        pid=$(cat "$lockfile")
+        echo "devenv up is already running''${pid:+ (pid $pid)}" 1>&2
+        exit 1
+      fi
+      # the lock file holds our pid only while we hold the lock
+      : > "$lockfile"
+      echo $$ >&9
+      trap ': > "$lockfile"' EXIT
       shell
-      eval "$env"
+      # keep whatever enterShell starts in the background from holding the lock
+      eval "$env" 9>&-
       procfilescript=$($CUSTOM_NIX/bin/nix $NIX_FLAGS build --no-link --print-out-paths --impure '.#procfileScript')
       cat $procfilescript
       if [ "$(cat $procfilescript|tail -n +2)" = "" ]; then
@@ -77,6 +92,8 @@ pkgs.writeScriptBin "devenv" ''
         exit 1
       else
         add_gc procfilescript $procfilescript
+        # the process manager inherits the lock, so it is held for as long as
+        # the processes run, even if this script dies first
         $procfilescript
       fi
       ;;
@@ -85,11 +102,23 @@ pkgs.writeScriptBin "devenv" ''
       echo "$env"
     ;;
     shell)
+      if [[ "$1" == "--command-file" && ( ! -r "$2" || -d "$2" ) ]]; then
+        echo "Command file '$2' does not exist, is not readable or is a directory." 1>&2
+        exit 1
+      fi
+      if [[ "$1" == "--command" && -z "$2" ]]; then
+        echo "devenv shell --command needs a script to run." 1>&2
+        exit 1
+      fi
       shell
       if [ $# -eq 0 ]; then
         echo "Entering shell ..." 1>&2
         echo "" 1>&2
         $CUSTOM_NIX/bin/nix $NIX_FLAGS develop "$DEVENV_GC/shell"
+      elif [[ "$1" == "--command-file" ]]; then
+        $CUSTOM_NIX/bin/nix $NIX_FLAGS develop "$DEVENV_GC/shell" -c bash "$2" "''${@:3}"
+      elif [[ "$1" == "--command" ]]; then
+        $CUSTOM_NIX/bin/nix $NIX_FLAGS develop "$DEVENV_GC/shell" -c bash -c "$2" devenv "''${@:3}"
       else
         set -e
         $CUSTOM_NIX/bin/nix $NIX_FLAGS develop "$DEVENV_GC/shell" -c "$@"
@@ -176,11 +205,39 @@ pkgs.writeScriptBin "devenv" ''
       ;;
     update)
       assemble
-      $CUSTOM_NIX/bin/nix $NIX_FLAGS flake update
+      if [[ "$1" == "--keep-going" ]]; then
+        # locking the first input would otherwise fetch all of them at once
+        if [[ ! -f "$FLAKE_LOCK" ]]; then
+          echo "--keep-going needs an existing $FLAKE_LOCK, run devenv update first." 1>&2
+          exit 1
+        fi
+        # the inputs of the generated flake: those of src/flake.nix merged with devenv.yaml's
+        if ! inputs=$($CUSTOM_NIX/bin/nix $NIX_FLAGS eval --impure --raw --expr \
+          'builtins.concatStringsSep "\n" (builtins.attrNames (import ./'"$FLAKE_FILE"').inputs)'); then
+          echo "Failed to read the inputs of $FLAKE_FILE." 1>&2
+          exit 1
+        fi
+        failed=()
+        for input in $inputs; do
+          if ! $CUSTOM_NIX/bin/nix $NIX_FLAGS flake lock --update-input "$input"; then
+            echo "Failed to update input '$input', skipping." 1>&2
+            failed+=("$input")
+          fi
+        done
+        if [ ''${#failed[@]} -ne 0 ]; then
+          echo "Failed to update inputs: ''${failed[*]}" 1>&2
+          exit 1
+        fi
+      else
+        $CUSTOM_NIX/bin/nix $NIX_FLAGS flake update
+      fi
       ;;
     version)
       echo "devenv: ${version}"
       ;;
+    generate-yaml-schema)
+      cat ${./devenv.schema.json}
+      ;;
     ci)
       assemble
       ci=$($CUSTOM_NIX/bin/nix $NIX_FLAGS build --no-link --print-out-paths '.#ci' --impure)
@@ -233,12 +290,20 @@ pkgs.writeScriptBin "devenv" ''
       echo "search NAME:    Search packages matching NAME in nixpkgs input."
       echo "shell:          Activate the developer environment."
       echo "shell CMD ARGS: Run CMD with ARGS in the developer environment. Useful when scripting."
+      echo "shell --command-file FILE ARGS:"
+      echo "                Run the bash script FILE with ARGS in the developer environment."
+      echo "shell --command SCRIPT ARGS:"
+      echo "                Run the bash SCRIPT string with ARGS in the developer environment."
       echo "info:           Print information about the current developer environment."
       echo "update:         Update devenv.lock from devenv.yaml inputs. See http://devenv.sh/inputs/#locking-and-updating-inputs"
+      echo "update --keep-going:"
+      echo "                Update each input separately, skipping those that fail."
       echo "up:             Starts processes in foreground. See http://devenv.sh/processes"
       echo "gc:             Removes old devenv generations. See http://devenv.sh/garbage-collection"
       echo "ci:             builds your developer environment and make sure all checks pass."
       echo "version:        Display devenv version"
+      echo "generate-yaml-schema:"
+      echo "                Print the JSON Schema of devenv.yaml."
       echo
       exit 1
   esac